@@ -1,99 +1,508 @@
 // Rust Benchmark Suite
 // Comparable to C++/Nevaarize benchmarks
 
-use std::time::Instant;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
-fn bench_int() {
-    let limit: i64 = 1_000_000_000;
-    let start = Instant::now();
+/// Numerically stable running mean/variance accumulator (Welford's online algorithm).
+///
+/// Samples are folded in one at a time via `push`, so the suite never has to
+/// retain the full sample vector just to compute a standard deviation.
+#[derive(Default)]
+struct MeanAndVariance {
+    n: i64,
+    mean: f64,
+    m2: f64,
+}
+
+impl MeanAndVariance {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, x: f64) {
+        self.n += 1;
+        let d = x - self.mean;
+        self.mean += d / self.n as f64;
+        let d2 = x - self.mean;
+        self.m2 += d * d2;
+    }
+
+    fn variance(&self) -> f64 {
+        if self.n > 1 {
+            self.m2 / (self.n - 1) as f64
+        } else {
+            0.0
+        }
+    }
+
+    fn stddev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// Coefficient of variation, as a percentage of the mean.
+    fn cv_percent(&self) -> f64 {
+        if self.mean != 0.0 {
+            100.0 * self.stddev() / self.mean
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Number of repeated runs per benchmark used to estimate mean/stddev.
+const RUNS: i64 = 30;
+
+/// Wall-clock duration each benchmark's calibrated run should take, so the
+/// suite stays balanced across fast and slow machines instead of spending
+/// microseconds on one workload and seconds on another.
+const TARGET_DURATION: Duration = Duration::from_millis(1000);
+
+/// Minimum burst duration before calibration trusts its own measurement.
+/// Below this, timer resolution and one-off scheduling noise dominate.
+const CALIBRATION_THRESHOLD: Duration = Duration::from_millis(50);
+
+/// Doubles the iteration count starting from 1 until a burst of `f` takes at
+/// least `CALIBRATION_THRESHOLD`, then scales that count so a real run should
+/// take roughly `target`. Replaces hand-tuned per-benchmark iteration limits.
+fn calibrate(target: Duration, mut f: impl FnMut()) -> i64 {
+    let mut iters: i64 = 1;
+    loop {
+        let start = Instant::now();
+        for _ in 0..iters {
+            f();
+        }
+        let elapsed = start.elapsed();
+        if elapsed >= CALIBRATION_THRESHOLD {
+            let scale = target.as_secs_f64() / elapsed.as_secs_f64();
+            return (((iters as f64) * scale).ceil() as i64).max(1);
+        }
+        iters *= 2;
+    }
+}
+
+/// One benchmark's calibrated, multi-sample result, kept structured so it
+/// can be rendered as a human table or serialized for cross-language
+/// comparison (see `OutputFormat`).
+struct BenchResult {
+    name: &'static str,
+    iters: i64,
+    /// Mean wall-clock time of a single sample run, in seconds. A driver
+    /// deriving OPS/sec as `iters / elapsed_secs` gets a figure close to, but
+    /// not identical with, `ops_mean`, which is the winsorized mean of the
+    /// per-run OPS samples.
+    elapsed_secs: f64,
+    ops_mean: f64,
+    ops_stddev: f64,
+    ops_cv_percent: f64,
+    ops_median: f64,
+    ops_min: f64,
+    ops_max: f64,
+    ops_iqr: f64,
+    mb_per_sec: Option<f64>,
+}
+
+/// Fraction of samples winsorized at each tail before recomputing the mean,
+/// so a single slow run doesn't dominate the headline OPS/sec figure.
+const WINSORIZE_FRACTION: f64 = 0.05;
+
+/// Linearly-interpolated percentile of an already-sorted slice (`p` in `0.0..=1.0`).
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.len() == 1 {
+        return sorted[0];
+    }
+    let idx = p * (sorted.len() - 1) as f64;
+    let lo = idx.floor() as usize;
+    let hi = idx.ceil() as usize;
+    if lo == hi {
+        sorted[lo]
+    } else {
+        let frac = idx - lo as f64;
+        sorted[lo] + (sorted[hi] - sorted[lo]) * frac
+    }
+}
+
+/// Robust summary of a sample set: median/min/max/IQR from the raw samples,
+/// plus a mean/stddev recomputed after winsorizing the lowest and highest
+/// `WINSORIZE_FRACTION` of samples to the 5th/95th percentile. Resists a
+/// one-off OS scheduling spike dominating the headline OPS/sec figure.
+struct Summary {
+    mean: f64,
+    stddev: f64,
+    cv_percent: f64,
+    median: f64,
+    min: f64,
+    max: f64,
+    iqr: f64,
+}
+
+fn summarize(mut samples: Vec<f64>) -> Summary {
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let min = samples[0];
+    let max = *samples.last().unwrap();
+    let median = percentile(&samples, 0.5);
+    let iqr = percentile(&samples, 0.75) - percentile(&samples, 0.25);
+    let low = percentile(&samples, WINSORIZE_FRACTION);
+    let high = percentile(&samples, 1.0 - WINSORIZE_FRACTION);
+    let mut winsorized = MeanAndVariance::new();
+    for &x in &samples {
+        winsorized.push(x.clamp(low, high));
+    }
+    Summary {
+        mean: winsorized.mean,
+        stddev: winsorized.stddev(),
+        cv_percent: winsorized.cv_percent(),
+        median,
+        min,
+        max,
+        iqr,
+    }
+}
+
+/// Calibrates and repeatedly times one benchmark, returning its result.
+///
+/// `body` performs one unit of work per call and receives the iteration
+/// index, counting from 0 at the start of every sample run; benchmarks that
+/// need to reset accumulated state (e.g. a growable `Vec`/`String`) can do
+/// so on `i == 0` instead of wiring up a separate reset hook. `bytes_per_iter`,
+/// when set, reports MB/s throughput alongside OPS/sec.
+fn run(name: &'static str, bytes_per_iter: Option<usize>, mut body: impl FnMut(i64)) -> BenchResult {
+    let mut counter: i64 = 0;
+    let iters = calibrate(TARGET_DURATION, || {
+        body(counter);
+        counter += 1;
+    });
+    let mut ops_samples = Vec::with_capacity(RUNS as usize);
+    let mut mb_samples = bytes_per_iter.map(|_| Vec::with_capacity(RUNS as usize));
+    let mut elapsed_total = 0.0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        for i in 0..iters {
+            body(i);
+        }
+        let elapsed = start.elapsed().as_secs_f64();
+        elapsed_total += elapsed;
+        let ops = iters as f64 / elapsed;
+        ops_samples.push(ops);
+        if let (Some(bytes), Some(samples)) = (bytes_per_iter, mb_samples.as_mut()) {
+            samples.push(bytes as f64 * ops / 1_000_000.0);
+        }
+    }
+    let elapsed_secs = elapsed_total / RUNS as f64;
+    let stats = summarize(ops_samples);
+    BenchResult {
+        name,
+        iters,
+        elapsed_secs,
+        ops_mean: stats.mean,
+        ops_stddev: stats.stddev,
+        ops_cv_percent: stats.cv_percent,
+        ops_median: stats.median,
+        ops_min: stats.min,
+        ops_max: stats.max,
+        ops_iqr: stats.iqr,
+        mb_per_sec: mb_samples.map(|samples| summarize(samples).mean),
+    }
+}
+
+fn bench_int() -> BenchResult {
     let mut i: i64 = 0;
-    while i < limit {
+    run("Integer Add", None, |_| {
         std::hint::black_box(i);
         i += 1;
-    }
-    let elapsed = start.elapsed().as_secs_f64();
-    let ops = limit as f64 / elapsed;
-    println!("  Integer Add     | {:>15.2} OPS/sec | {:.4}s", ops, elapsed);
+    })
 }
 
-fn bench_double() {
-    let limit: i64 = 100_000_000;
-    let start = Instant::now();
+fn bench_double() -> BenchResult {
     let mut val: f64 = 0.0;
-    let mut j: i64 = 0;
-    while j < limit {
+    let result = run("Double Arith", None, |_| {
         val += 1.1;
-        j += 1;
-    }
-    let elapsed = start.elapsed().as_secs_f64();
-    let ops = limit as f64 / elapsed;
-    println!("  Double Arith    | {:>15.2} OPS/sec | {:.4}s", ops, elapsed);
+    });
     // Prevent optimization
-    if val < 0.0 { println!("{}", val); }
+    if val < 0.0 {
+        println!("{}", val);
+    }
+    result
 }
 
-fn bench_string() {
-    let limit: i64 = 50_000;
-    let start = Instant::now();
-    let mut s = String::new();
-    let mut i: i64 = 0;
-    while i < limit {
+/// Working-set size for append-style benchmarks (`bench_string`,
+/// `bench_array`): the buffer is cleared every `APPEND_WORKING_SET` pushes
+/// instead of only once per sample run, so calibration and the timed runs
+/// both measure steady-state push cost against a bounded buffer rather than
+/// calibrating unbounded growth out to wall-clock (which, at a ~1s target,
+/// would otherwise push hundreds of millions of elements per run).
+const APPEND_WORKING_SET: i64 = 10_000;
+
+fn bench_string() -> BenchResult {
+    let mut s = String::with_capacity(APPEND_WORKING_SET as usize);
+    let result = run("String Concat", Some(1), |i| {
+        if i % APPEND_WORKING_SET == 0 {
+            s.clear();
+        }
         s.push('a');
-        i += 1;
-    }
-    let elapsed = start.elapsed().as_secs_f64();
-    let ops = limit as f64 / elapsed;
-    println!("  String Concat   | {:>15.2} OPS/sec | {:.4}s", ops, elapsed);
+    });
     // Prevent optimization
-    if s.len() == 0 { println!("{}", s); }
+    if s.is_empty() {
+        println!("{}", s);
+    }
+    result
 }
 
-fn bench_array() {
-    let limit: i64 = 1_000_000;
-    let start = Instant::now();
-    let mut arr: Vec<i64> = Vec::new();
-    let mut k: i64 = 0;
-    while k < limit {
-        arr.push(k);
-        k += 1;
-    }
-    let elapsed = start.elapsed().as_secs_f64();
-    let ops = limit as f64 / elapsed;
-    println!("  Array Push      | {:>15.2} OPS/sec | {:.4}s", ops, elapsed);
+fn bench_array() -> BenchResult {
+    let mut arr: Vec<i64> = Vec::with_capacity(APPEND_WORKING_SET as usize);
+    let result = run("Array Push", Some(std::mem::size_of::<i64>()), |i| {
+        if i % APPEND_WORKING_SET == 0 {
+            arr.clear();
+        }
+        arr.push(i);
+    });
     // Prevent optimization
-    if arr.len() == 0 { println!("{}", arr[0]); }
+    if arr.is_empty() {
+        println!("{}", arr[0]);
+    }
+    result
 }
 
 struct Obj {
     val: i64,
 }
 
-fn bench_struct() {
-    let limit: i64 = 50_000_000;
-    let start = Instant::now();
+fn bench_struct() -> BenchResult {
     let mut o = Obj { val: 0 };
-    let mut i: i64 = 0;
-    while i < limit {
-        o.val = i;
+    run("Struct Access", None, |_| {
+        o.val += 1;
         let x = o.val;
         std::hint::black_box(x);
-        i += 1;
+    })
+}
+
+/// Side length of the flood-fill benchmark's grid.
+const FLOODFILL_SIZE: usize = 200;
+
+/// A connected region counts as "large" when it has more than this many cells.
+const FLOODFILL_REGION_THRESHOLD: usize = 20;
+
+/// Tiny xorshift PRNG so the flood-fill grid pattern is reproducible without
+/// pulling in an external crate.
+fn xorshift(state: &mut u64) -> u64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    *state
+}
+
+/// Builds a deterministic `size x size` grid of small integer values (0..4)
+/// for `bench_floodfill` to scan for connected regions.
+fn build_floodfill_grid(size: usize) -> Vec<Vec<u8>> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..size)
+        .map(|_| (0..size).map(|_| (xorshift(&mut state) % 4) as u8).collect())
+        .collect()
+}
+
+/// Control-flow/memory-bound workload: a BFS flood fill over a fixed grid,
+/// counting connected regions above `FLOODFILL_REGION_THRESHOLD` cells. Unlike
+/// the arithmetic/string/array benchmarks, this stresses bounds checks, queue
+/// churn, and less predictable memory access patterns.
+///
+/// Each call to `run`'s body visits exactly one cell, so the harness's
+/// calibrated "OPS/sec" figure here is cells-visited/sec. Once the whole grid
+/// has been scanned, the scan state resets and flood fill runs again from the
+/// top-left, so the benchmark keeps producing cells to visit for as long as
+/// calibration or the sample runs need.
+fn bench_floodfill() -> BenchResult {
+    let grid = build_floodfill_grid(FLOODFILL_SIZE);
+    let size = grid.len();
+    let mut marked = vec![vec![false; size]; size];
+    let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+    let mut scan_row = 0usize;
+    let mut scan_col = 0usize;
+    let mut region_size = 0usize;
+    let mut regions = 0usize;
+
+    let result = run("Flood Fill", None, |iter_idx| {
+        if iter_idx == 0 {
+            for row in marked.iter_mut() {
+                row.fill(false);
+            }
+            queue.clear();
+            scan_row = 0;
+            scan_col = 0;
+            region_size = 0;
+            regions = 0;
+        }
+        if queue.is_empty() {
+            if region_size > FLOODFILL_REGION_THRESHOLD {
+                regions += 1;
+            }
+            region_size = 0;
+            while scan_row < size && marked[scan_row][scan_col] {
+                scan_col += 1;
+                if scan_col == size {
+                    scan_col = 0;
+                    scan_row += 1;
+                }
+            }
+            if scan_row == size {
+                // Whole grid scanned; wrap around so later samples keep going.
+                for row in marked.iter_mut() {
+                    row.fill(false);
+                }
+                scan_row = 0;
+                scan_col = 0;
+            }
+            marked[scan_row][scan_col] = true;
+            queue.push_back((scan_row, scan_col));
+        }
+        let (i, j) = queue.pop_front().unwrap();
+        region_size += 1;
+        let value = grid[i][j];
+        if i > 0 && !marked[i - 1][j] && grid[i - 1][j] == value {
+            marked[i - 1][j] = true;
+            queue.push_back((i - 1, j));
+        }
+        if i + 1 < size && !marked[i + 1][j] && grid[i + 1][j] == value {
+            marked[i + 1][j] = true;
+            queue.push_back((i + 1, j));
+        }
+        if j > 0 && !marked[i][j - 1] && grid[i][j - 1] == value {
+            marked[i][j - 1] = true;
+            queue.push_back((i, j - 1));
+        }
+        if j + 1 < size && !marked[i][j + 1] && grid[i][j + 1] == value {
+            marked[i][j + 1] = true;
+            queue.push_back((i, j + 1));
+        }
+    });
+    // Prevent optimization
+    if regions == usize::MAX {
+        println!("{}", regions);
     }
-    let elapsed = start.elapsed().as_secs_f64();
-    let ops = limit as f64 / elapsed;
-    println!("  Struct Access   | {:>15.2} OPS/sec | {:.4}s", ops, elapsed);
+    result
 }
 
-fn main() {
+/// Output mode for the suite's results, selected via `--format` or the
+/// `BENCH_FORMAT` env var so a driver script can merge this suite's numbers
+/// with the C++/Nevaarize suites without scraping aligned text columns.
+#[derive(Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl OutputFormat {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "table" => Some(Self::Table),
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Reads `--format <fmt>` / `--format=<fmt>` from argv, falling back to the
+/// `BENCH_FORMAT` env var, then `table` if neither is set or recognized.
+fn output_format() -> OutputFormat {
+    let args: Vec<String> = std::env::args().collect();
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(value) = arg.strip_prefix("--format=") {
+            if let Some(format) = OutputFormat::parse(value) {
+                return format;
+            }
+        } else if arg == "--format" {
+            if let Some(value) = args.get(i + 1).and_then(|v| OutputFormat::parse(v)) {
+                return value;
+            }
+        }
+    }
+    if let Ok(value) = std::env::var("BENCH_FORMAT") {
+        if let Some(format) = OutputFormat::parse(&value) {
+            return format;
+        }
+    }
+    OutputFormat::Table
+}
+
+fn print_table(results: &[BenchResult]) {
     println!(">>> Rust Benchmark Suite <<<");
     println!("  -------------------------------------------------------------");
-    println!("  Benchmark       |     Performance | Time");
+    println!(
+        "  Benchmark       |     Performance (mean \u{b1} CV, median over {} runs)",
+        RUNS
+    );
     println!("  -------------------------------------------------------------");
-    bench_int();
-    bench_double();
-    bench_string();
-    bench_array();
-    bench_struct();
+    for r in results {
+        match r.mb_per_sec {
+            Some(mb_per_sec) => println!(
+                "  {:<15} | {:>15.2} \u{b1} {:>4.1}% OPS/sec (median {:>15.2}) | {:>8.2} MB/s",
+                r.name, r.ops_mean, r.ops_cv_percent, r.ops_median, mb_per_sec
+            ),
+            None => println!(
+                "  {:<15} | {:>15.2} \u{b1} {:>4.1}% OPS/sec (median {:>15.2})",
+                r.name, r.ops_mean, r.ops_cv_percent, r.ops_median
+            ),
+        }
+    }
     println!("  -------------------------------------------------------------");
     println!();
 }
+
+fn print_json(results: &[BenchResult]) {
+    println!("[");
+    for (i, r) in results.iter().enumerate() {
+        let comma = if i + 1 < results.len() { "," } else { "" };
+        println!(
+            "  {{\"name\": \"{}\", \"iters\": {}, \"elapsed_secs\": {:.6}, \"ops_mean\": {:.4}, \"ops_stddev\": {:.4}, \"ops_median\": {:.4}, \"ops_min\": {:.4}, \"ops_max\": {:.4}, \"ops_iqr\": {:.4}, \"mb_per_sec\": {}}}{}",
+            r.name,
+            r.iters,
+            r.elapsed_secs,
+            r.ops_mean,
+            r.ops_stddev,
+            r.ops_median,
+            r.ops_min,
+            r.ops_max,
+            r.ops_iqr,
+            r.mb_per_sec.map_or("null".to_string(), |v| format!("{:.4}", v)),
+            comma
+        );
+    }
+    println!("]");
+}
+
+fn print_csv(results: &[BenchResult]) {
+    println!("name,iters,elapsed_secs,ops_mean,ops_stddev,ops_median,ops_min,ops_max,ops_iqr,mb_per_sec");
+    for r in results {
+        println!(
+            "{},{},{:.6},{:.4},{:.4},{:.4},{:.4},{:.4},{:.4},{}",
+            r.name,
+            r.iters,
+            r.elapsed_secs,
+            r.ops_mean,
+            r.ops_stddev,
+            r.ops_median,
+            r.ops_min,
+            r.ops_max,
+            r.ops_iqr,
+            r.mb_per_sec.map_or(String::new(), |v| format!("{:.4}", v))
+        );
+    }
+}
+
+fn main() {
+    let format = output_format();
+    let results = vec![
+        bench_int(),
+        bench_double(),
+        bench_string(),
+        bench_array(),
+        bench_struct(),
+        bench_floodfill(),
+    ];
+    match format {
+        OutputFormat::Table => print_table(&results),
+        OutputFormat::Json => print_json(&results),
+        OutputFormat::Csv => print_csv(&results),
+    }
+}